@@ -0,0 +1,43 @@
+mod webrtc_socket;
+
+pub use webrtc_socket::{
+    BuildablePlurality, ChannelConfig, ChannelPlurality, CustomSignalHandler, MultipleChannels,
+    NoChannels, Packet, PeerId, PeerState, RtcIceServerConfig, SingleChannel, WebRtcChannel,
+    WebRtcSocket, WebRtcSocketBuilder,
+};
+pub use webrtc_socket::rate_limit::RateLimitConfig;
+
+use std::fmt;
+
+use webrtc_socket::error::{MessagingError, SignalingError};
+
+/// Top-level error returned by a [`WebRtcSocketBuilder::build`] message
+/// loop future.
+#[derive(Debug)]
+pub enum Error {
+    Signaling(SignalingError),
+    Messaging(MessagingError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Signaling(err) => write!(f, "signaling error: {err}"),
+            Error::Messaging(err) => write!(f, "messaging error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<SignalingError> for Error {
+    fn from(err: SignalingError) -> Self {
+        Error::Signaling(err)
+    }
+}
+
+impl From<MessagingError> for Error {
+    fn from(err: MessagingError) -> Self {
+        Error::Messaging(err)
+    }
+}
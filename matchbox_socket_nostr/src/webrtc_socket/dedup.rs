@@ -0,0 +1,94 @@
+use std::collections::{HashSet, VecDeque};
+
+use nostr::EventId;
+
+/// A bounded set of recently-seen [`EventId`]s, used to drop duplicate
+/// relay events when the same message arrives from more than one relay
+/// in the pool.
+///
+/// Eviction is oldest-first once `capacity` is reached, so memory stays
+/// bounded even under a long-lived connection to many relays.
+pub(crate) struct EventDedup {
+    capacity: usize,
+    seen: HashSet<EventId>,
+    order: VecDeque<EventId>,
+}
+
+impl EventDedup {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns `true` the first time `id` is seen, `false` on every
+    /// subsequent call, recording `id` as seen either way.
+    pub(crate) fn insert(&mut self, id: EventId) -> bool {
+        if !self.seen.insert(id) {
+            return false;
+        }
+
+        self.order.push_back(id);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostr::{Keys, Kind, Timestamp};
+
+    fn event_id(seed: u8) -> EventId {
+        let keys = Keys::generate();
+        let created_at = Timestamp::from(seed as u64);
+        EventId::new(
+            &keys.public_key(),
+            created_at,
+            &Kind::EncryptedDirectMessage,
+            &[],
+            "content",
+        )
+    }
+
+    #[test]
+    fn first_insert_is_new() {
+        let mut dedup = EventDedup::new(4);
+        assert!(dedup.insert(event_id(1)));
+    }
+
+    #[test]
+    fn duplicate_insert_is_not_new() {
+        let mut dedup = EventDedup::new(4);
+        let id = event_id(1);
+        assert!(dedup.insert(id));
+        assert!(!dedup.insert(id));
+    }
+
+    #[test]
+    fn eviction_is_oldest_first() {
+        let mut dedup = EventDedup::new(2);
+        let first = event_id(1);
+        let second = event_id(2);
+        let third = event_id(3);
+
+        assert!(dedup.insert(first));
+        assert!(dedup.insert(second));
+        // Pushes `first` out once capacity is exceeded.
+        assert!(dedup.insert(third));
+
+        // `first` was evicted, so it's treated as new again - which in
+        // turn evicts `second`.
+        assert!(dedup.insert(first));
+        assert!(dedup.insert(second));
+        // Re-inserting `second` evicted `third`, so it's new again too.
+        assert!(dedup.insert(third));
+    }
+}
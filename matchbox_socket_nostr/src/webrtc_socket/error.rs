@@ -0,0 +1,85 @@
+use std::fmt;
+
+/// Errors that can occur while talking to the Nostr relay(s) used for
+/// signaling.
+///
+/// Variants are split into transient (worth reconnecting for) and fatal
+/// (reconnecting to the same relay won't help) so `signaling_loop` can
+/// decide whether to retry a single relay or give up on the whole
+/// socket. See [`SignalingError::is_transient`].
+#[derive(Debug)]
+pub enum SignalingError {
+    /// The relay sent a frame that wasn't a text message, e.g. a stray
+    /// ping frame. Not fatal: the message is simply ignored.
+    UnknownFormat,
+
+    // --- transient: reconnecting the affected relay is likely to help ---
+    /// The underlying websocket connection was reset or closed.
+    ConnectionReset(String),
+    /// A read or write on the relay connection timed out.
+    Timeout,
+    /// A single send to the relay failed, e.g. a transient socket error.
+    SendFailed(String),
+
+    // --- fatal: reconnecting won't change the outcome ---
+    /// The configured nostr keypair or room url is invalid.
+    InvalidConfig(String),
+    /// A local channel used to move events between tasks was torn down.
+    ChannelClosed,
+}
+
+impl SignalingError {
+    /// Whether this error represents a momentary hiccup worth retrying
+    /// against the same relay, as opposed to a problem that reconnecting
+    /// won't fix.
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            SignalingError::ConnectionReset(_)
+                | SignalingError::Timeout
+                | SignalingError::SendFailed(_)
+        )
+    }
+}
+
+impl fmt::Display for SignalingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalingError::UnknownFormat => write!(f, "unknown message format"),
+            SignalingError::ConnectionReset(reason) => write!(f, "relay connection reset: {reason}"),
+            SignalingError::Timeout => write!(f, "relay connection timed out"),
+            SignalingError::SendFailed(reason) => write!(f, "failed to send to relay: {reason}"),
+            SignalingError::InvalidConfig(reason) => write!(f, "invalid signaling config: {reason}"),
+            SignalingError::ChannelClosed => write!(f, "an internal signaling channel was closed"),
+        }
+    }
+}
+
+impl std::error::Error for SignalingError {}
+
+impl<T> From<futures_channel::mpsc::TrySendError<T>> for SignalingError {
+    fn from(_err: futures_channel::mpsc::TrySendError<T>) -> Self {
+        SignalingError::ChannelClosed
+    }
+}
+
+/// Errors that can occur while sending a packet over an established
+/// peer data channel.
+#[derive(Debug)]
+pub enum MessagingError {
+    /// The data channel (or the task driving it) is gone.
+    ChannelClosed,
+    /// Sending the packet failed at the transport layer.
+    Other(String),
+}
+
+impl fmt::Display for MessagingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessagingError::ChannelClosed => write!(f, "data channel is closed"),
+            MessagingError::Other(reason) => write!(f, "failed to send packet: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for MessagingError {}
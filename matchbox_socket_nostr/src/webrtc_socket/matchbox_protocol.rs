@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// Identifies a peer in the signaling protocol. Wraps the Nostr public
+/// key the peer signs its signaling events with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PeerId(pub nostr::secp256k1::XOnlyPublicKey);
+
+/// WebRTC session negotiation data exchanged between two peers via the
+/// signaling channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PeerSignal {
+    Offer(String),
+    Answer(String),
+    IceCandidate(String),
+}
+
+/// A request from a signaling client to be relayed to another peer.
+/// `KeepAlive` is queued by the message loop on its keep-alive interval;
+/// the signaling loop does not forward it to any relay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PeerRequest<S = PeerSignal> {
+    Signal { receiver: PeerId, data: S },
+    /// An application-defined control message, see
+    /// [`crate::webrtc_socket::CustomSignalHandler`].
+    Custom { receiver: PeerId, data: Vec<u8> },
+    KeepAlive,
+}
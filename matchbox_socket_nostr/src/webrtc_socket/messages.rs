@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+pub(crate) use crate::webrtc_socket::matchbox_protocol::{PeerId, PeerRequest, PeerSignal};
+
+/// An event delivered up from the signaling loop to the message loop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum PeerEvent<S = PeerSignal> {
+    /// Our own peer id, assigned once at startup.
+    IdAssigned(PeerId),
+    /// A new peer announced itself and a handshake should be offered.
+    NewPeer(PeerId),
+    /// A peer disconnected or its relay subscription ended.
+    PeerLeft(PeerId),
+    /// Signaling data relayed from another peer's handshake.
+    Signal { sender: PeerId, data: S },
+    /// A peer was dropped for exceeding its signaling rate limit, see
+    /// [`crate::webrtc_socket::rate_limit::SignalRateLimiter`].
+    PeerBlacklisted(PeerId),
+}
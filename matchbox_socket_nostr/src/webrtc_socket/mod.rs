@@ -1,10 +1,14 @@
+mod dedup;
 pub(crate) mod error;
-mod matchbox_protocol;
-mod messages;
-mod signal_peer;
+pub(crate) mod matchbox_protocol;
+pub(crate) mod messages;
+pub(crate) mod rate_limit;
+pub(crate) mod signal_peer;
 mod socket;
 
+use self::dedup::EventDedup;
 use self::error::{MessagingError, SignalingError};
+use self::rate_limit::{RateLimitConfig, RateLimitDecision, SignalRateLimiter};
 use crate::{webrtc_socket::signal_peer::SignalPeer, Error};
 use async_trait::async_trait;
 use cfg_if::cfg_if;
@@ -24,22 +28,27 @@ pub use socket::{
     BuildablePlurality, ChannelConfig, ChannelPlurality, MultipleChannels, NoChannels, PeerState,
     RtcIceServerConfig, SingleChannel, WebRtcChannel, WebRtcSocket, WebRtcSocketBuilder,
 };
-use std::{collections::HashMap, pin::Pin, time::Duration};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
 
 cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         use nostr::prelude::*;
         mod wasm;
-        type UseMessenger = wasm::WasmMessenger;
-        type UseSignaller = wasm::WasmSignaller;
+        pub(crate) type UseMessenger = wasm::WasmMessenger;
+        pub(crate) type UseSignaller = wasm::WasmSignaller;
         /// A future which runs the message loop for the socket and completes
         /// when the socket closes or disconnects
 
         pub type MessageLoopFuture = Pin<Box<dyn Future<Output = Result<(), Error>>>>;
     } else {
         mod native;
-        type UseMessenger = native::NativeMessenger;
-        type UseSignaller = native::NativeSignaller;
+        pub(crate) type UseMessenger = native::NativeMessenger;
+        pub(crate) type UseSignaller = native::NativeSignaller;
         /// A future which runs the message loop for the socket and completes
         /// when the socket closes or disconnects
         pub type MessageLoopFuture = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
@@ -53,36 +62,124 @@ trait Signaller: Sized {
 
     async fn send(&mut self, request: String) -> Result<(), SignalingError>;
 
+    /// Returns `Err(SignalingError::UnknownFormat)` for non-text frames
+    /// (text that isn't valid relay JSON is returned as `Ok` and
+    /// skipped by the caller), `Err` of a transient kind (see [`SignalingError::is_transient`])
+    /// for recoverable connection hiccups, and `Err` of a fatal kind for
+    /// everything else.
     async fn next_message(&mut self) -> Result<String, SignalingError>;
 }
 
-async fn signaling_loop<S: Signaller>(
+/// A handler for application-defined control messages carried alongside
+/// the WebRTC signaling traffic on the same Nostr channel.
+///
+/// This lets games exchange lobby/matchmaking metadata with a peer
+/// before any data channel exists, without opening a second relay
+/// connection.
+pub trait CustomSignalHandler: Send + Sync {
+    /// Called for every decrypted custom payload received from `from`.
+    fn handle(&self, from: PeerId, payload: &[u8]);
+}
+
+/// The Nostr tag used to mark custom application messages so they can be
+/// told apart from handshake `Signal` messages on the wire.
+const CUSTOM_SIGNAL_TAG: &str = "matchbox-nostr-custom-1";
+
+/// Number of recently-seen relay event ids to remember so that the same
+/// DM arriving from several relays in the pool is only processed once.
+const RELAY_DEDUP_CAPACITY: usize = 256;
+
+/// Base delay for the first reconnect attempt after a transient relay
+/// error, doubled on each subsequent attempt up to `RECONNECT_MAX_DELAY`.
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(250);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Exponential backoff with jitter for relay reconnect attempts, so a
+/// pool of clients reconnecting to the same relay after an outage
+/// doesn't all retry in lockstep.
+fn reconnect_backoff(attempt: u32) -> Duration {
+    let exp = RECONNECT_BASE_DELAY
+        .checked_mul(1u32 << attempt.min(31))
+        .unwrap_or(RECONNECT_MAX_DELAY)
+        .min(RECONNECT_MAX_DELAY);
+
+    let jitter_ceiling_ms = (exp.as_millis() as u64 / 2).max(1);
+    let jitter_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos() as u64
+        % jitter_ceiling_ms;
+
+    exp + Duration::from_millis(jitter_ms)
+}
+
+/// Retries `S::new` against `room_url` with backoff until it succeeds.
+/// Runs as a tracked background future so a single dead relay never
+/// blocks polling of the rest of the pool.
+async fn reconnect_relay<S: Signaller>(attempts: Option<u16>, room_url: &str) -> S {
+    let mut attempt = 0u32;
+    loop {
+        Delay::new(reconnect_backoff(attempt)).await;
+        match S::new(attempts, room_url).await {
+            Ok(signaller) => return signaller,
+            Err(err) => {
+                warn!("reconnect attempt {attempt} to {room_url:?} failed: {err:?}");
+                attempt = attempt.saturating_add(1);
+            }
+        }
+    }
+}
+
+pub(crate) async fn signaling_loop<S: Signaller>(
     attempts: Option<u16>,
-    room_url: String,
+    room_urls: Vec<String>,
     mut requests_receiver: futures_channel::mpsc::UnboundedReceiver<PeerRequest>,
     events_sender: futures_channel::mpsc::UnboundedSender<PeerEvent>,
     nostr_keys: Keys,
+    custom_handler: Option<Arc<dyn CustomSignalHandler>>,
+    rate_limit_config: RateLimitConfig,
 ) -> Result<(), SignalingError> {
     use nostr::prelude::*;
 
-    let mut signaller = S::new(attempts, &room_url).await?;
-    debug!("room {:?}", room_url);
+    let mut signallers: Vec<Option<S>> = futures::future::try_join_all(
+        room_urls.iter().map(|room_url| S::new(attempts, room_url)),
+    )
+    .await?
+    .into_iter()
+    .map(Some)
+    .collect();
+    debug!("relay pool {:?}", room_urls);
+
+    // Relays currently being reconnected after a transient error. Polled
+    // alongside everything else so one dead relay reconnecting never
+    // blocks requests_receiver or any other relay's next_message().
+    let mut reconnecting = FuturesUnordered::new();
+
+    let mut seen_events = EventDedup::new(RELAY_DEDUP_CAPACITY);
+    let mut rate_limiter = SignalRateLimiter::new(rate_limit_config);
 
     let pub_key = PeerId(nostr_keys.public_key());
     let tag = "matchbox-nostr-1";
 
-    let id = uuid::Uuid::new_v4();
-    let subscribe = ClientMessage::new_req(
-        SubscriptionId::new(id.to_string()),
-        vec![Filter::new()
-            .kind(Kind::EncryptedDirectMessage)
-            .since(Timestamp::now())],
-    );
-
-    signaller
-        .send(subscribe.as_json())
-        .await
-        .map_err(SignalingError::from)?;
+    // Builds a fresh subscription starting from "now", so a relay we
+    // reconnect to after a blip doesn't replay events we already saw.
+    fn build_subscribe() -> ClientMessage {
+        let id = uuid::Uuid::new_v4();
+        ClientMessage::new_req(
+            SubscriptionId::new(id.to_string()),
+            vec![Filter::new()
+                .kind(Kind::EncryptedDirectMessage)
+                .since(Timestamp::now())],
+        )
+    }
+
+    let subscribe = build_subscribe();
+    for signaller in signallers.iter_mut().filter_map(|s| s.as_mut()) {
+        signaller
+            .send(subscribe.as_json())
+            .await
+            .map_err(SignalingError::from)?;
+    }
     debug!("subscribing to {:?}", subscribe);
 
     //add id and send peer message
@@ -93,17 +190,52 @@ async fn signaling_loop<S: Signaller>(
         .map_err(SignalingError::from)?;
 
     loop {
+        let mut live_messages = signallers
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(relay, signaller)| signaller.as_mut().map(|signaller| (relay, signaller)))
+            .map(|(relay, signaller)| async move { (relay, signaller.next_message().await) })
+            .collect::<FuturesUnordered<_>>();
+
+        // `FuturesUnordered::next()` resolves to `None` immediately when
+        // the collection is empty, rather than staying pending. Every
+        // relay can be simultaneously `None` here (taken out of rotation
+        // while reconnecting) - most commonly the single-relay default
+        // setup right after its one relay hits a transient error - so
+        // polling an empty collection must not be mistaken for "nothing
+        // left to do" and fall through to the terminal `None` arm below.
+        let any_live = !live_messages.is_empty();
+        let next_message = async move {
+            if any_live {
+                live_messages.next().await
+            } else {
+                std::future::pending().await
+            }
+        }
+        .fuse();
+        futures::pin_mut!(next_message);
+
         select! {
             request = requests_receiver.next().fuse() => {
 
-            if let Some(matchbox_protocol::PeerRequest::Signal { receiver, data: _ }) = &request {
+            let receiver_and_tag = match &request {
+                Some(matchbox_protocol::PeerRequest::Signal { receiver, data: _ }) => {
+                    Some((receiver, tag))
+                }
+                Some(PeerRequest::Custom { receiver, data: _ }) => {
+                    Some((receiver, CUSTOM_SIGNAL_TAG))
+                }
+                _ => None,
+            };
+
+            if let Some((receiver, message_tag)) = receiver_and_tag {
 
                 let request = serde_json::to_string(&request).expect("serializing request");
 
                 let created_at = Timestamp::now();
                 let kind = Kind::EncryptedDirectMessage;
 
-                let tags = vec![Tag::PubKey(receiver.0, None ), Tag::Hashtag(tag.to_string())];
+                let tags = vec![Tag::PubKey(receiver.0, None ), Tag::Hashtag(message_tag.to_string())];
 
                 let content =
                 encrypt(&nostr_keys.secret_key().unwrap(), &receiver.0, request).unwrap();
@@ -129,12 +261,45 @@ async fn signaling_loop<S: Signaller>(
                 // Log the message being sent
                 warn!("SENDING...{msg:?}");
 
-                // Send the message and handle possible errors
-                signaller.send(msg.as_json()).await.map_err(SignalingError::from)?;
+                // Broadcast to every relay in the pool so signaling survives
+                // any single relay dropping or censoring the message. Relays
+                // currently reconnecting are skipped; they'll pick up the
+                // subscription again once reconnected.
+                for signaller in signallers.iter_mut().filter_map(|s| s.as_mut()) {
+                    if let Err(err) = signaller.send(msg.as_json()).await {
+                        warn!("failed to send to relay: {err:?}");
+                    }
+                }
             }
         }
 
-             message = signaller.next_message().fuse() => {
+            reconnected = reconnecting.select_next_some() => {
+                let (relay, mut new_signaller) = reconnected;
+
+                let subscribe = build_subscribe();
+                if let Err(err) = new_signaller.send(subscribe.as_json()).await {
+                    warn!("failed to re-subscribe to relay {relay} after reconnecting: {err:?}");
+                } else {
+                    debug!("re-subscribed to {:?} on relay {relay}", subscribe);
+                }
+                signallers[relay] = Some(new_signaller);
+
+                let reconnected_pub_key = PeerId(nostr_keys.public_key());
+                if reconnected_pub_key != pub_key {
+                    events_sender
+                        .unbounded_send(PeerEvent::IdAssigned(reconnected_pub_key))
+                        .map_err(SignalingError::from)?;
+                }
+            }
+
+             next_message = next_message => {
+
+                let Some((relay, message)) = next_message else {
+                    // `any_live` gates this future to `pending()` whenever
+                    // no relay is live, so it can only resolve once a
+                    // live relay's `next_message()` actually completes.
+                    unreachable!("next_message only resolves while a relay is live, and never yields None in that case");
+                };
 
                 match message {
 
@@ -145,30 +310,50 @@ async fn signaling_loop<S: Signaller>(
                                     event,
                                     subscription_id: _,
                                 } => {
-                                    if event.pubkey == nostr_keys.public_key() {
+                                    if !seen_events.insert(event.id) {
+                                        debug!("ignoring duplicate event {:?} from another relay", event.id);
+                                    } else if event.pubkey == nostr_keys.public_key() {
                                     } else if event.kind == Kind::EncryptedDirectMessage {
-                                        warn!("RECEIVED..{event:?}");
-                                        if let Ok(msg) = decrypt(
-                                            &nostr_keys.secret_key().unwrap(),
-                                            &event.pubkey,
-                                            event.content,
-                                        ) {
-                                        let peer_key = event.pubkey;
-                                        if let Ok(event) = serde_json::from_str::<PeerRequest>(&msg) {
-                                            match event {
-                                                PeerRequest::Signal{receiver: _, data } => {
-                                                    let event = PeerEvent::Signal {
-                                                        sender: PeerId(peer_key),
-                                                        data,
-                                                        };
-                                                    events_sender.unbounded_send(event).map_err(SignalingError::from)?;
+                                        match rate_limiter.check(event.pubkey) {
+                                            RateLimitDecision::Drop => {
+                                                debug!("dropping event from {:?}: rate limit exceeded", event.pubkey);
+                                            }
+                                            RateLimitDecision::Blacklisted => {
+                                                warn!("blacklisting {:?} after repeated signaling rate-limit violations", event.pubkey);
+                                                events_sender
+                                                    .unbounded_send(PeerEvent::PeerBlacklisted(PeerId(event.pubkey)))
+                                                    .map_err(SignalingError::from)?;
+                                            }
+                                            RateLimitDecision::Allow => {
+                                                warn!("RECEIVED..{event:?}");
+                                                if let Ok(msg) = decrypt(
+                                                    &nostr_keys.secret_key().unwrap(),
+                                                    &event.pubkey,
+                                                    event.content,
+                                                ) {
+                                                let peer_key = event.pubkey;
+                                                if let Ok(event) = serde_json::from_str::<PeerRequest>(&msg) {
+                                                    match event {
+                                                        PeerRequest::Signal{receiver: _, data } => {
+                                                            let event = PeerEvent::Signal {
+                                                                sender: PeerId(peer_key),
+                                                                data,
+                                                                };
+                                                            events_sender.unbounded_send(event).map_err(SignalingError::from)?;
+                                                        }
+                                                        PeerRequest::Custom{receiver: _, data } => {
+                                                            if let Some(handler) = &custom_handler {
+                                                                handler.handle(PeerId(peer_key), &data);
+                                                            }
+                                                        }
+                                                        PeerRequest::KeepAlive => {}
+                                                     }
+                                                } else if let Ok(new_peer) = serde_json::from_str::<PeerEvent>(&msg) {
+
+                                                    events_sender.unbounded_send(new_peer).map_err(SignalingError::from)?;
                                                 }
-                                                PeerRequest::KeepAlive => {}
-                                             }
-                                        } else if let Ok(new_peer) = serde_json::from_str::<PeerEvent>(&msg) {
-
-                                            events_sender.unbounded_send(new_peer).map_err(SignalingError::from)?;
-                                        }
+                                                }
+                                            }
                                         }
                                     }
                                }
@@ -209,6 +394,20 @@ async fn signaling_loop<S: Signaller>(
                     Err(SignalingError::UnknownFormat) => {
                         warn!("ignoring unexpected non-text message from signaling server")
                     }
+                    Err(err) if err.is_transient() => {
+                        warn!("transient signaling error on relay {relay} ({:?}), reconnecting in the background", room_urls[relay]);
+
+                        // Take the relay's slot out of rotation and hand the
+                        // reconnect off to `reconnecting` so it's retried
+                        // alongside, not instead of, every other relay and
+                        // requests_receiver.
+                        signallers[relay] = None;
+                        let room_url = room_urls[relay].clone();
+                        reconnecting.push(async move {
+                            let signaller = reconnect_relay::<S>(attempts, &room_url).await;
+                            (relay, signaller)
+                        });
+                    }
                     Err(err) => {
                         break Err(err)
                     }
@@ -226,6 +425,12 @@ pub type Packet = Box<[u8]>;
 
 trait PeerDataSender {
     fn send(&mut self, packet: Packet) -> Result<(), MessagingError>;
+
+    /// Bytes currently queued for this channel at the transport layer
+    /// (mirrors `RTCDataChannel.bufferedAmount`), used to apply
+    /// backpressure instead of letting an unbounded queue build up
+    /// behind a slow or stalled peer.
+    fn buffered_amount(&self) -> usize;
 }
 
 struct HandshakeResult<D: PeerDataSender, M> {
@@ -259,13 +464,14 @@ trait Messenger {
     async fn peer_loop(peer_uuid: PeerId, handshake_meta: Self::HandshakeMeta) -> PeerId;
 }
 
-async fn message_loop<M: Messenger>(
+pub(crate) async fn message_loop<M: Messenger>(
     id_tx: crossbeam_channel::Sender<PeerId>,
     ice_server_config: &RtcIceServerConfig,
     channel_configs: &[ChannelConfig],
     channels: MessageLoopChannels,
     keep_alive_interval: Option<Duration>,
-) {
+    max_in_flight_handshakes: usize,
+) -> Result<(), MessagingError> {
     let MessageLoopChannels {
         requests_sender,
         mut events_receiver,
@@ -279,6 +485,11 @@ async fn message_loop<M: Messenger>(
     let mut handshake_signals = HashMap::new();
     let mut data_channels = HashMap::new();
 
+    // Packets held back from a congested peer's channel, and the set of
+    // (peer, channel) pairs currently being throttled.
+    let mut pending_out: HashMap<(PeerId, usize), VecDeque<Packet>> = HashMap::new();
+    let mut congested: HashSet<(PeerId, usize)> = HashSet::new();
+
     let mut timeout = if let Some(interval) = keep_alive_interval {
         Either::Left(Delay::new(interval))
     } else {
@@ -287,6 +498,36 @@ async fn message_loop<M: Messenger>(
     .fuse();
 
     loop {
+        // Retry any packets held back for congested peers before pulling in
+        // new ones, so a peer that drains its buffer catches back up.
+        pending_out.retain(|&(peer, channel_index), queue| {
+            let Some(data_channel) = data_channels
+                .get_mut(&peer)
+                .and_then(|channels: &mut Vec<_>| channels.get_mut(channel_index))
+            else {
+                return false;
+            };
+
+            while data_channel.buffered_amount() <= channel_configs[channel_index].outbound_low_water_mark {
+                let Some(packet) = queue.pop_front() else {
+                    break;
+                };
+                if let Err(err) = data_channel.send(packet) {
+                    warn!("failed to send queued packet to peer {peer:?}: {err:?}");
+                }
+            }
+
+            if queue.is_empty() {
+                congested.remove(&(peer, channel_index));
+                peer_state_tx
+                    .unbounded_send((peer, PeerState::Connected))
+                    .expect("failed to report peer as no longer congested");
+                false
+            } else {
+                true
+            }
+        });
+
         let mut next_peer_messages_out = peer_messages_out_rx
             .iter_mut()
             .enumerate()
@@ -311,25 +552,41 @@ async fn message_loop<M: Messenger>(
                             id_tx.try_send(peer_uuid.to_owned()).unwrap();
                         },
                         PeerEvent::NewPeer(peer_uuid) => {
-
-                            let (signal_tx, signal_rx) = futures_channel::mpsc::unbounded();
-                            handshake_signals.insert(peer_uuid, signal_tx);
-                            let signal_peer = SignalPeer::new(peer_uuid, requests_sender.clone());
-                            handshakes.push(M::offer_handshake(signal_peer, signal_rx, messages_from_peers_tx.clone(), ice_server_config, channel_configs))
+                            if handshake_signals.len() >= max_in_flight_handshakes {
+                                warn!("refusing to start a handshake with {peer_uuid:?}: too many handshakes already in flight");
+                            } else {
+                                let (signal_tx, signal_rx) = futures_channel::mpsc::unbounded();
+                                handshake_signals.insert(peer_uuid, signal_tx);
+                                let signal_peer = SignalPeer::new(peer_uuid, requests_sender.clone());
+                                handshakes.push(M::offer_handshake(signal_peer, signal_rx, messages_from_peers_tx.clone(), ice_server_config, channel_configs))
+                            }
+                        },
+                        PeerEvent::PeerLeft(peer_uuid) => {
+                            handshake_signals.remove(&peer_uuid);
+                            peer_state_tx.unbounded_send((peer_uuid, PeerState::Disconnected)).expect("fail to report peer as disconnected");
                         },
-                        PeerEvent::PeerLeft(peer_uuid) => {peer_state_tx.unbounded_send((peer_uuid, PeerState::Disconnected)).expect("fail to report peer as disconnected");},
                         PeerEvent::Signal { sender, data } => {
-                            let signal_tx = handshake_signals.entry(sender).or_insert_with(|| {
-                                let (from_peer_tx, peer_signal_rx) = futures_channel::mpsc::unbounded();
-                                let signal_peer = SignalPeer::new(sender, requests_sender.clone());
-                                handshakes.push(M::accept_handshake(signal_peer, peer_signal_rx, messages_from_peers_tx.clone(), ice_server_config, channel_configs));
-                                from_peer_tx
-                            });
-
-                            if signal_tx.unbounded_send(data).is_err() {
+                            if data_channels.contains_key(&sender) {
                                 warn!("ignoring signal from peer {sender:?} because the handshake has already finished");
+                            } else if !handshake_signals.contains_key(&sender) && handshake_signals.len() >= max_in_flight_handshakes {
+                                warn!("refusing to start a handshake with {sender:?}: too many handshakes already in flight");
+                            } else {
+                                let signal_tx = handshake_signals.entry(sender).or_insert_with(|| {
+                                    let (from_peer_tx, peer_signal_rx) = futures_channel::mpsc::unbounded();
+                                    let signal_peer = SignalPeer::new(sender, requests_sender.clone());
+                                    handshakes.push(M::accept_handshake(signal_peer, peer_signal_rx, messages_from_peers_tx.clone(), ice_server_config, channel_configs));
+                                    from_peer_tx
+                                });
+
+                                if signal_tx.unbounded_send(data).is_err() {
+                                    warn!("ignoring signal from peer {sender:?} because the handshake has already finished");
+                                }
                             }
                         },
+                        PeerEvent::PeerBlacklisted(peer_uuid) => {
+                            warn!("dropping peer {peer_uuid:?}: blacklisted for signaling abuse");
+                            handshake_signals.remove(&peer_uuid);
+                        },
                     }
                 }
             }
@@ -337,6 +594,9 @@ async fn message_loop<M: Messenger>(
 
 
             handshake_result = handshakes.select_next_some() => {
+                // The handshake is done, so it's no longer "in flight" -
+                // free its slot against max_in_flight_handshakes.
+                handshake_signals.remove(&handshake_result.peer_id);
                 data_channels.insert(handshake_result.peer_id, handshake_result.data_channels);
                 peer_state_tx.unbounded_send((handshake_result.peer_id, PeerState::Connected)).expect("failed to report peer as connected");
                 peer_loops.push(M::peer_loop(handshake_result.peer_id, handshake_result.metadata));
@@ -350,11 +610,31 @@ async fn message_loop<M: Messenger>(
             message = next_peer_message_out => {
                 match message {
                     Some((channel_index, Some((peer, packet)))) => {
+                        let key = (peer, channel_index);
                         let data_channel = data_channels
                             .get_mut(&peer)
                             .expect("couldn't find data channel for peer")
                             .get_mut(channel_index).unwrap_or_else(|| panic!("couldn't find data channel with index {channel_index}"));
-                        data_channel.send(packet).unwrap();
+
+                        if congested.contains(&key) {
+                            pending_out.entry(key).or_default().push_back(packet);
+                        } else if data_channel.buffered_amount() > channel_configs[channel_index].outbound_high_water_mark {
+                            warn!("peer {peer:?} channel {channel_index} exceeded the outbound high-water mark, applying backpressure");
+                            congested.insert(key);
+                            pending_out.entry(key).or_default().push_back(packet);
+                            peer_state_tx
+                                .unbounded_send((peer, PeerState::Congested))
+                                .expect("failed to report peer as congested");
+                        } else if let Err(err) = data_channel.send(packet) {
+                            // A hard send failure only means this one peer's
+                            // channel is gone - report it as disconnected
+                            // instead of tearing down every other peer's
+                            // connection along with it.
+                            warn!("failed to send packet to peer {peer:?}: {err:?}, marking it disconnected");
+                            peer_state_tx
+                                .unbounded_send((peer, PeerState::Disconnected))
+                                .expect("failed to report peer as disconnected");
+                        }
 
                     }
                     Some((_, None)) | None => {
@@ -363,12 +643,88 @@ async fn message_loop<M: Messenger>(
                         // There could probably be cleaner ways to handle this,
                         // but for now, just exit cleanly.
                         debug!("Outgoing message queue closed");
-                        break;
+                        break Ok(());
                     }
                 }
             }
 
-            complete => break
+            complete => break Ok(())
         }
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FAKE_SIGNALLER_INSTANCES: AtomicUsize = AtomicUsize::new(0);
+
+    /// A `Signaller` whose first instance's `next_message` yields exactly
+    /// one transient error before hanging forever - standing in for a
+    /// relay that hiccups once and then has nothing more to say. Every
+    /// reconnected instance hangs forever from the start.
+    struct FakeSignaller {
+        is_first: bool,
+        errored: bool,
+    }
+
+    #[async_trait]
+    impl Signaller for FakeSignaller {
+        async fn new(_attempts: Option<u16>, _room_url: &str) -> Result<Self, SignalingError> {
+            let instance = FAKE_SIGNALLER_INSTANCES.fetch_add(1, Ordering::SeqCst);
+            Ok(FakeSignaller {
+                is_first: instance == 0,
+                errored: false,
+            })
+        }
+
+        async fn send(&mut self, _request: String) -> Result<(), SignalingError> {
+            Ok(())
+        }
+
+        async fn next_message(&mut self) -> Result<String, SignalingError> {
+            if self.is_first && !self.errored {
+                self.errored = true;
+                return Err(SignalingError::ConnectionReset("fake transient blip".into()));
+            }
+            std::future::pending().await
+        }
+    }
+
+    /// Regression test for a bug where `signaling_loop` mistook an empty
+    /// `FuturesUnordered` (every relay momentarily taken out of rotation
+    /// while reconnecting) for "nothing left to do" and silently ended
+    /// the whole loop right after the first transient error - which on
+    /// the default single-relay setup happened on the very next tick.
+    #[tokio::test]
+    async fn signaling_loop_survives_transient_error_on_sole_relay() {
+        FAKE_SIGNALLER_INSTANCES.store(0, Ordering::SeqCst);
+
+        let (requests_tx, requests_rx) = futures_channel::mpsc::unbounded();
+        let (events_tx, _events_rx) = futures_channel::mpsc::unbounded();
+        let keys = Keys::generate();
+
+        let loop_fut = signaling_loop::<FakeSignaller>(
+            None,
+            vec!["wss://fake-relay.invalid".to_string()],
+            requests_rx,
+            events_tx,
+            keys,
+            None,
+            RateLimitConfig::default(),
+        );
+
+        // A correct loop never returns here: it should still be waiting
+        // on the reconnect and on requests_receiver long after the sole
+        // relay's one transient error. Race it against a generous
+        // timeout rather than expecting it to resolve.
+        let outcome = tokio::time::timeout(Duration::from_secs(2), loop_fut).await;
+
+        drop(requests_tx);
+        assert!(
+            outcome.is_err(),
+            "signaling_loop terminated after a single transient error instead of reconnecting in the background"
+        );
+    }
+}
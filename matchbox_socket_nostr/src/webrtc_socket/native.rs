@@ -0,0 +1,242 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, warn};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message as WsMessage, MaybeTlsStream, WebSocketStream};
+use webrtc::{
+    api::APIBuilder,
+    data_channel::RTCDataChannel,
+    ice_transport::{ice_candidate::RTCIceCandidateInit, ice_server::RTCIceServer},
+    peer_connection::{
+        configuration::RTCConfiguration, sdp::session_description::RTCSessionDescription,
+    },
+};
+
+use crate::webrtc_socket::{
+    error::SignalingError, matchbox_protocol::PeerSignal, messages::PeerId, signal_peer::SignalPeer,
+    ChannelConfig, HandshakeResult, Messenger, Packet, PeerDataSender, RtcIceServerConfig, Signaller,
+};
+
+/// Opens one Nostr relay WebSocket connection over native TCP.
+pub(crate) struct NativeSignaller {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+#[async_trait]
+impl Signaller for NativeSignaller {
+    async fn new(mut attempts: Option<u16>, room_url: &str) -> Result<Self, SignalingError> {
+        loop {
+            match tokio_tungstenite::connect_async(room_url).await {
+                Ok((socket, _response)) => return Ok(Self { socket }),
+                Err(err) => {
+                    if let Some(attempts) = attempts.as_mut() {
+                        if *attempts == 0 {
+                            return Err(SignalingError::ConnectionReset(err.to_string()));
+                        }
+                        *attempts -= 1;
+                    }
+                    warn!("failed to connect to relay {room_url}: {err}, retrying");
+                }
+            }
+        }
+    }
+
+    async fn send(&mut self, request: String) -> Result<(), SignalingError> {
+        self.socket
+            .send(WsMessage::Text(request))
+            .await
+            .map_err(|err| SignalingError::SendFailed(err.to_string()))
+    }
+
+    async fn next_message(&mut self) -> Result<String, SignalingError> {
+        match self.socket.next().await {
+            Some(Ok(WsMessage::Text(message))) => Ok(message),
+            Some(Ok(WsMessage::Close(_))) | None => {
+                Err(SignalingError::ConnectionReset("relay connection closed".to_string()))
+            }
+            Some(Ok(_)) => Err(SignalingError::UnknownFormat),
+            Some(Err(err)) => Err(SignalingError::ConnectionReset(err.to_string())),
+        }
+    }
+}
+
+fn build_rtc_config(ice_server_config: &RtcIceServerConfig) -> RTCConfiguration {
+    RTCConfiguration {
+        ice_servers: vec![RTCIceServer {
+            urls: ice_server_config.urls.clone(),
+            username: ice_server_config.username.clone().unwrap_or_default(),
+            credential: ice_server_config.credential.clone().unwrap_or_default(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+pub(crate) struct DataChannel {
+    channel: Arc<RTCDataChannel>,
+}
+
+impl PeerDataSender for DataChannel {
+    fn send(&mut self, packet: Packet) -> Result<(), crate::webrtc_socket::error::MessagingError> {
+        let channel = self.channel.clone();
+        let bytes = bytes::Bytes::from(packet.into_vec());
+        tokio::spawn(async move {
+            if let Err(err) = channel.send(&bytes).await {
+                warn!("failed to send on data channel: {err}");
+            }
+        });
+        Ok(())
+    }
+
+    fn buffered_amount(&self) -> usize {
+        self.channel.buffered_amount()
+    }
+}
+
+pub(crate) struct NativeMessenger;
+
+#[async_trait]
+impl Messenger for NativeMessenger {
+    type DataChannel = DataChannel;
+    type HandshakeMeta = ();
+
+    async fn offer_handshake(
+        signal_peer: SignalPeer,
+        mut peer_signal_rx: UnboundedReceiver<PeerSignal>,
+        messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+        ice_server_config: &RtcIceServerConfig,
+        channel_configs: &[ChannelConfig],
+    ) -> HandshakeResult<Self::DataChannel, Self::HandshakeMeta> {
+        negotiate(
+            true,
+            signal_peer,
+            &mut peer_signal_rx,
+            messages_from_peers_tx,
+            ice_server_config,
+            channel_configs,
+        )
+        .await
+    }
+
+    async fn accept_handshake(
+        signal_peer: SignalPeer,
+        mut peer_signal_rx: UnboundedReceiver<PeerSignal>,
+        messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+        ice_server_config: &RtcIceServerConfig,
+        channel_configs: &[ChannelConfig],
+    ) -> HandshakeResult<Self::DataChannel, Self::HandshakeMeta> {
+        negotiate(
+            false,
+            signal_peer,
+            &mut peer_signal_rx,
+            messages_from_peers_tx,
+            ice_server_config,
+            channel_configs,
+        )
+        .await
+    }
+
+    async fn peer_loop(peer_uuid: PeerId, _handshake_meta: Self::HandshakeMeta) -> PeerId {
+        peer_uuid
+    }
+}
+
+async fn negotiate(
+    offerer: bool,
+    signal_peer: SignalPeer,
+    peer_signal_rx: &mut UnboundedReceiver<PeerSignal>,
+    messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+    ice_server_config: &RtcIceServerConfig,
+    channel_configs: &[ChannelConfig],
+) -> HandshakeResult<DataChannel, ()> {
+    use futures::StreamExt as _;
+
+    let api = APIBuilder::new().build();
+    let connection = Arc::new(
+        api.new_peer_connection(build_rtc_config(ice_server_config))
+            .await
+            .expect("failed to create peer connection"),
+    );
+
+    let mut data_channels = Vec::with_capacity(channel_configs.len());
+    for (index, config) in channel_configs.iter().enumerate() {
+        let channel = connection
+            .create_data_channel(
+                &format!("matchbox-{index}"),
+                Some(webrtc::data_channel::data_channel_init::RTCDataChannelInit {
+                    ordered: Some(config.ordered),
+                    max_retransmits: config.max_retransmits,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .expect("failed to create data channel");
+
+        let tx = messages_from_peers_tx[index].clone();
+        let peer_id = signal_peer.peer_id();
+        channel.on_message(Box::new(move |msg| {
+            let tx = tx.clone();
+            let packet: Packet = msg.data.to_vec().into_boxed_slice();
+            Box::pin(async move {
+                let _ = tx.unbounded_send((peer_id, packet));
+            })
+        }));
+
+        data_channels.push(DataChannel { channel });
+    }
+
+    if offerer {
+        let offer = connection.create_offer(None).await.expect("failed to create offer");
+        connection
+            .set_local_description(offer.clone())
+            .await
+            .expect("failed to set local description");
+        signal_peer.send(PeerSignal::Offer(offer.sdp));
+    }
+
+    while let Some(signal) = peer_signal_rx.next().await {
+        match signal {
+            PeerSignal::Offer(sdp) => {
+                let description = RTCSessionDescription::offer(sdp).expect("invalid remote offer");
+                connection
+                    .set_remote_description(description)
+                    .await
+                    .expect("failed to set remote description");
+                let answer = connection.create_answer(None).await.expect("failed to create answer");
+                connection
+                    .set_local_description(answer.clone())
+                    .await
+                    .expect("failed to set local description");
+                signal_peer.send(PeerSignal::Answer(answer.sdp));
+            }
+            PeerSignal::Answer(sdp) => {
+                let description = RTCSessionDescription::answer(sdp).expect("invalid remote answer");
+                connection
+                    .set_remote_description(description)
+                    .await
+                    .expect("failed to set remote description");
+                break;
+            }
+            PeerSignal::IceCandidate(candidate) => {
+                let candidate = RTCIceCandidateInit {
+                    candidate,
+                    ..Default::default()
+                };
+                if let Err(err) = connection.add_ice_candidate(candidate).await {
+                    warn!("failed to add remote ice candidate: {err}");
+                }
+            }
+        }
+    }
+
+    debug!("handshake with {:?} complete", signal_peer.peer_id());
+
+    HandshakeResult {
+        peer_id: signal_peer.peer_id(),
+        data_channels,
+        metadata: (),
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use nostr::secp256k1::XOnlyPublicKey;
+
+/// Tunable limits for [`SignalRateLimiter`].
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Maximum sustained rate of relay events accepted from a single
+    /// pubkey, in events per `interval`.
+    pub events_per_interval: u32,
+    pub interval: Duration,
+    /// Extra events a pubkey may send in a single burst on top of its
+    /// steady-state allowance.
+    pub burst: u32,
+    /// How long a pubkey that exceeds its bucket stays blacklisted.
+    pub blacklist_cooldown: Duration,
+    /// Number of consecutive bucket violations at which a pubkey is
+    /// blacklisted (the violation that reaches it triggers the blacklist).
+    pub violations_before_blacklist: u32,
+    /// How long a pubkey's bucket is kept after its last activity before
+    /// being evicted, so a long-running socket contacted by many distinct
+    /// one-off peers doesn't accumulate buckets forever.
+    pub bucket_idle_timeout: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            events_per_interval: 20,
+            interval: Duration::from_secs(1),
+            burst: 10,
+            blacklist_cooldown: Duration::from_secs(60),
+            violations_before_blacklist: 5,
+            bucket_idle_timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+    violations: u32,
+}
+
+/// Per-pubkey token bucket rate limiting and handshake-flood protection
+/// for inbound relay events, so a single malicious pubkey can't flood the
+/// signaling loop or cause unbounded handshake growth.
+pub(crate) struct SignalRateLimiter {
+    config: RateLimitConfig,
+    buckets: HashMap<XOnlyPublicKey, TokenBucket>,
+    blacklist: HashMap<XOnlyPublicKey, Instant>,
+}
+
+/// What to do with an inbound event from a given pubkey.
+pub(crate) enum RateLimitDecision {
+    Allow,
+    /// The pubkey exceeded its bucket; the event should be dropped.
+    Drop,
+    /// The pubkey just crossed the violation threshold and is now
+    /// blacklisted for `blacklist_cooldown`.
+    Blacklisted,
+}
+
+impl SignalRateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: HashMap::new(),
+            blacklist: HashMap::new(),
+        }
+    }
+
+    /// Checks whether an event from `pubkey` should be processed, or
+    /// dropped because the sender's bucket is empty or it is currently
+    /// cooling down after a blacklist.
+    pub(crate) fn check(&mut self, pubkey: XOnlyPublicKey) -> RateLimitDecision {
+        let now = Instant::now();
+
+        if let Some(&cooldown_until) = self.blacklist.get(&pubkey) {
+            if now < cooldown_until {
+                return RateLimitDecision::Drop;
+            }
+            self.blacklist.remove(&pubkey);
+        }
+
+        let max_tokens = (self.config.events_per_interval + self.config.burst) as f64;
+        let refill_rate = self.config.events_per_interval as f64 / self.config.interval.as_secs_f64();
+
+        // Evict buckets that have been idle long enough that a pubkey
+        // seen only briefly doesn't keep one around forever.
+        let idle_timeout = self.config.bucket_idle_timeout;
+        self.buckets
+            .retain(|_, bucket| now.duration_since(bucket.last_refill) < idle_timeout);
+
+        let bucket = self.buckets.entry(pubkey).or_insert_with(|| TokenBucket {
+            tokens: max_tokens,
+            last_refill: now,
+            violations: 0,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_rate).min(max_tokens);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.violations = 0;
+            RateLimitDecision::Allow
+        } else {
+            bucket.violations += 1;
+            if bucket.violations >= self.config.violations_before_blacklist {
+                self.blacklist
+                    .insert(pubkey, now + self.config.blacklist_cooldown);
+                self.buckets.remove(&pubkey);
+                RateLimitDecision::Blacklisted
+            } else {
+                RateLimitDecision::Drop
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pubkey(_seed: u8) -> XOnlyPublicKey {
+        nostr::Keys::generate().public_key()
+    }
+
+    #[test]
+    fn allows_events_within_burst() {
+        let config = RateLimitConfig {
+            events_per_interval: 5,
+            interval: Duration::from_secs(1),
+            burst: 2,
+            ..RateLimitConfig::default()
+        };
+        let mut limiter = SignalRateLimiter::new(config);
+        let pubkey = test_pubkey(1);
+
+        for _ in 0..7 {
+            assert!(matches!(limiter.check(pubkey), RateLimitDecision::Allow));
+        }
+        assert!(matches!(limiter.check(pubkey), RateLimitDecision::Drop));
+    }
+
+    #[test]
+    fn blacklists_after_repeated_violations() {
+        let config = RateLimitConfig {
+            events_per_interval: 1,
+            interval: Duration::from_secs(1),
+            burst: 0,
+            violations_before_blacklist: 3,
+            ..RateLimitConfig::default()
+        };
+        let mut limiter = SignalRateLimiter::new(config);
+        let pubkey = test_pubkey(2);
+
+        assert!(matches!(limiter.check(pubkey), RateLimitDecision::Allow));
+        assert!(matches!(limiter.check(pubkey), RateLimitDecision::Drop));
+        assert!(matches!(limiter.check(pubkey), RateLimitDecision::Drop));
+        assert!(matches!(limiter.check(pubkey), RateLimitDecision::Blacklisted));
+
+        // Still cooling down immediately after being blacklisted.
+        assert!(matches!(limiter.check(pubkey), RateLimitDecision::Drop));
+    }
+
+    #[test]
+    fn idle_buckets_are_evicted() {
+        let config = RateLimitConfig {
+            bucket_idle_timeout: Duration::from_nanos(1),
+            ..RateLimitConfig::default()
+        };
+        let mut limiter = SignalRateLimiter::new(config);
+        let first = test_pubkey(3);
+        let second = test_pubkey(4);
+
+        limiter.check(first);
+        assert_eq!(limiter.buckets.len(), 1);
+
+        // `first`'s bucket is already older than the idle timeout, so
+        // checking a different pubkey sweeps it away.
+        std::thread::sleep(Duration::from_millis(1));
+        limiter.check(second);
+        assert_eq!(limiter.buckets.len(), 1);
+        assert!(!limiter.buckets.contains_key(&first));
+    }
+}
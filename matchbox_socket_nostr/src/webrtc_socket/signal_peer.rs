@@ -0,0 +1,39 @@
+use futures_channel::mpsc::UnboundedSender;
+
+use crate::webrtc_socket::{messages::PeerRequest, PeerId};
+
+/// A handle a handshake/peer loop uses to send signaling data to one
+/// specific remote peer over the shared signaling channel.
+#[derive(Clone)]
+pub(crate) struct SignalPeer {
+    peer_id: PeerId,
+    requests_sender: UnboundedSender<PeerRequest>,
+}
+
+impl SignalPeer {
+    pub(crate) fn new(peer_id: PeerId, requests_sender: UnboundedSender<PeerRequest>) -> Self {
+        Self {
+            peer_id,
+            requests_sender,
+        }
+    }
+
+    /// The id of the peer this handle sends signals to.
+    pub(crate) fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+
+    /// Sends `signal` to this peer via the signaling channel.
+    pub(crate) fn send(&self, signal: crate::webrtc_socket::matchbox_protocol::PeerSignal) {
+        if self
+            .requests_sender
+            .unbounded_send(PeerRequest::Signal {
+                receiver: self.peer_id,
+                data: signal,
+            })
+            .is_err()
+        {
+            log::warn!("failed to queue signal for peer {:?}: signaling loop is gone", self.peer_id);
+        }
+    }
+}
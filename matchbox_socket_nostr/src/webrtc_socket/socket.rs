@@ -0,0 +1,374 @@
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+use futures::FutureExt;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use nostr::Keys;
+
+use crate::{
+    webrtc_socket::{
+        matchbox_protocol::PeerRequest, message_loop, messages::PeerEvent, rate_limit::RateLimitConfig,
+        signaling_loop, CustomSignalHandler, MessageLoopFuture, Packet, UseMessenger, UseSignaller,
+    },
+    Error, PeerId,
+};
+
+/// Configuration for a single data channel: whether packets are
+/// delivered in order, how many times an unordered packet is
+/// retransmitted, and the backpressure thresholds applied to it.
+#[derive(Debug, Clone)]
+pub struct ChannelConfig {
+    pub ordered: bool,
+    pub max_retransmits: Option<u16>,
+    /// Bytes queued for a peer on this channel above which it is
+    /// considered congested and outbound packets are held back instead
+    /// of being handed to the transport.
+    pub outbound_high_water_mark: usize,
+    /// Bytes queued at or below which a congested peer on this channel
+    /// resumes being sent to directly.
+    pub outbound_low_water_mark: usize,
+}
+
+impl ChannelConfig {
+    /// Ordered, reliable delivery - packets always arrive, in order.
+    pub fn reliable() -> Self {
+        Self {
+            ordered: true,
+            max_retransmits: None,
+            outbound_high_water_mark: 256 * 1024,
+            outbound_low_water_mark: 64 * 1024,
+        }
+    }
+
+    /// Unordered, unreliable delivery with no retransmits - suitable for
+    /// frequent state updates where only the latest value matters.
+    pub fn unreliable() -> Self {
+        Self {
+            ordered: false,
+            max_retransmits: Some(0),
+            outbound_high_water_mark: 256 * 1024,
+            outbound_low_water_mark: 64 * 1024,
+        }
+    }
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self::reliable()
+    }
+}
+
+/// The ICE servers used to negotiate a WebRTC connection through NATs.
+#[derive(Debug, Clone, Default)]
+pub struct RtcIceServerConfig {
+    pub urls: Vec<String>,
+    pub username: Option<String>,
+    pub credential: Option<String>,
+}
+
+/// Connectivity state of a single peer, as reported by the message loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerState {
+    Connected,
+    Disconnected,
+    /// The peer's outbound buffer crossed a channel's high-water mark;
+    /// sends to it are being queued locally until it drains.
+    Congested,
+}
+
+/// Marker trait for the builder's channel-count typestate.
+pub trait ChannelPlurality {}
+/// Marker trait for typestates from which `build()` is callable (i.e.
+/// at least one channel has been configured).
+pub trait BuildablePlurality: ChannelPlurality {}
+
+/// No channels configured yet.
+pub struct NoChannels;
+/// Exactly one channel configured.
+pub struct SingleChannel;
+/// Two or more channels configured.
+pub struct MultipleChannels;
+
+impl ChannelPlurality for NoChannels {}
+impl ChannelPlurality for SingleChannel {}
+impl ChannelPlurality for MultipleChannels {}
+impl BuildablePlurality for SingleChannel {}
+impl BuildablePlurality for MultipleChannels {}
+
+/// A handle to one configured data channel: send packets to a peer, or
+/// drain packets received from peers on this channel.
+pub struct WebRtcChannel {
+    outbox: UnboundedSender<(PeerId, Packet)>,
+    inbox: UnboundedReceiver<(PeerId, Packet)>,
+}
+
+impl WebRtcChannel {
+    /// Queues `packet` to be sent to `peer` on this channel.
+    pub fn send(&self, packet: Packet, peer: PeerId) {
+        let _ = self.outbox.unbounded_send((peer, packet));
+    }
+
+    /// Drains all packets received on this channel since the last call.
+    pub fn receive(&mut self) -> Vec<(PeerId, Packet)> {
+        let mut packets = Vec::new();
+        while let Ok(Some(packet)) = self.inbox.try_next() {
+            packets.push(packet);
+        }
+        packets
+    }
+}
+
+/// The channels shared between the signaling loop and the message loop.
+pub(crate) struct MessageLoopChannels {
+    pub(crate) requests_sender: UnboundedSender<PeerRequest>,
+    pub(crate) events_receiver: UnboundedReceiver<PeerEvent>,
+    pub(crate) peer_messages_out_rx: Vec<UnboundedReceiver<(PeerId, Packet)>>,
+    pub(crate) messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+    pub(crate) peer_state_tx: UnboundedSender<(PeerId, PeerState)>,
+}
+
+/// A socket connected (via a pool of Nostr relays) to other peers
+/// running matchbox, with one [`WebRtcChannel`] per configured
+/// [`ChannelConfig`].
+pub struct WebRtcSocket {
+    id_rx: crossbeam_channel::Receiver<PeerId>,
+    id: Option<PeerId>,
+    peer_state_rx: UnboundedReceiver<(PeerId, PeerState)>,
+    channels: Vec<Option<WebRtcChannel>>,
+    requests_sender: UnboundedSender<PeerRequest>,
+}
+
+impl WebRtcSocket {
+    /// Our own peer id, once the signaling loop has assigned one.
+    pub fn id(&mut self) -> Option<PeerId> {
+        if self.id.is_none() {
+            self.id = self.id_rx.try_recv().ok();
+        }
+        self.id
+    }
+
+    /// Drains peer connectivity changes observed since the last call.
+    pub fn update_peers(&mut self) -> Vec<(PeerId, PeerState)> {
+        let mut updates = Vec::new();
+        while let Ok(Some(update)) = self.peer_state_rx.try_next() {
+            updates.push(update);
+        }
+        updates
+    }
+
+    /// Sends an application-defined custom payload to `peer`, delivered
+    /// to its [`CustomSignalHandler`] (if any) registered via
+    /// [`WebRtcSocketBuilder::on_custom_signal`]. Unlike [`WebRtcChannel`]
+    /// packets, this goes out over the signaling channel directly and
+    /// doesn't require a completed handshake with `peer`.
+    pub fn send_custom(&self, peer: PeerId, payload: Vec<u8>) {
+        let _ = self.requests_sender.unbounded_send(PeerRequest::Custom {
+            receiver: peer,
+            data: payload,
+        });
+    }
+
+    /// The channel configured at `index` (in the order passed to
+    /// [`WebRtcSocketBuilder::add_channel`]).
+    pub fn channel(&mut self, index: usize) -> &mut WebRtcChannel {
+        self.channels[index]
+            .as_mut()
+            .unwrap_or_else(|| panic!("no channel configured at index {index}"))
+    }
+}
+
+/// Builds a [`WebRtcSocket`] and the [`MessageLoopFuture`] that drives
+/// its signaling and data channels.
+pub struct WebRtcSocketBuilder<C: ChannelPlurality = NoChannels> {
+    room_urls: Vec<String>,
+    nostr_keys: Keys,
+    reconnect_attempts: Option<u16>,
+    ice_server_config: RtcIceServerConfig,
+    keep_alive_interval: Option<Duration>,
+    channel_configs: Vec<ChannelConfig>,
+    custom_handler: Option<Arc<dyn CustomSignalHandler>>,
+    rate_limit_config: RateLimitConfig,
+    max_in_flight_handshakes: usize,
+    _channel_plurality: PhantomData<C>,
+}
+
+impl WebRtcSocketBuilder<NoChannels> {
+    /// Connects to a single signaling relay.
+    pub fn new(room_url: impl Into<String>) -> Self {
+        Self::with_relay_pool(vec![room_url.into()])
+    }
+
+    /// Connects to a pool of signaling relays. Every relay gets the same
+    /// encrypted signaling events broadcast to it, and duplicate events
+    /// received from more than one relay are de-duplicated, so a single
+    /// flaky or censoring relay can't silently kill signaling.
+    pub fn with_relay_pool(room_urls: Vec<String>) -> Self {
+        Self {
+            room_urls,
+            nostr_keys: Keys::generate(),
+            reconnect_attempts: Some(3),
+            ice_server_config: RtcIceServerConfig::default(),
+            keep_alive_interval: Some(Duration::from_secs(10)),
+            channel_configs: Vec::new(),
+            custom_handler: None,
+            rate_limit_config: RateLimitConfig::default(),
+            max_in_flight_handshakes: 32,
+            _channel_plurality: PhantomData,
+        }
+    }
+}
+
+impl<C: ChannelPlurality> WebRtcSocketBuilder<C> {
+    pub fn nostr_keys(mut self, keys: Keys) -> Self {
+        self.nostr_keys = keys;
+        self
+    }
+
+    pub fn ice_server(mut self, config: RtcIceServerConfig) -> Self {
+        self.ice_server_config = config;
+        self
+    }
+
+    pub fn reconnect_attempts(mut self, attempts: Option<u16>) -> Self {
+        self.reconnect_attempts = attempts;
+        self
+    }
+
+    pub fn keep_alive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.keep_alive_interval = interval;
+        self
+    }
+
+    /// Registers a handler for application-defined control messages
+    /// carried alongside signaling traffic, see [`CustomSignalHandler`].
+    pub fn on_custom_signal(mut self, handler: Arc<dyn CustomSignalHandler>) -> Self {
+        self.custom_handler = Some(handler);
+        self
+    }
+
+    /// Limits for inbound signaling events and handshake-flood
+    /// protection, see [`RateLimitConfig`].
+    pub fn rate_limit_config(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limit_config = config;
+        self
+    }
+
+    /// Maximum number of handshakes that may be in flight at once before
+    /// new peers are refused.
+    pub fn max_in_flight_handshakes(mut self, max: usize) -> Self {
+        self.max_in_flight_handshakes = max;
+        self
+    }
+
+    fn push_channel<D: ChannelPlurality>(self, config: ChannelConfig) -> WebRtcSocketBuilder<D> {
+        let mut channel_configs = self.channel_configs;
+        channel_configs.push(config);
+        WebRtcSocketBuilder {
+            room_urls: self.room_urls,
+            nostr_keys: self.nostr_keys,
+            reconnect_attempts: self.reconnect_attempts,
+            ice_server_config: self.ice_server_config,
+            keep_alive_interval: self.keep_alive_interval,
+            channel_configs,
+            custom_handler: self.custom_handler,
+            rate_limit_config: self.rate_limit_config,
+            max_in_flight_handshakes: self.max_in_flight_handshakes,
+            _channel_plurality: PhantomData,
+        }
+    }
+}
+
+impl WebRtcSocketBuilder<NoChannels> {
+    pub fn add_channel(self, config: ChannelConfig) -> WebRtcSocketBuilder<SingleChannel> {
+        self.push_channel(config)
+    }
+}
+
+impl WebRtcSocketBuilder<SingleChannel> {
+    pub fn add_channel(self, config: ChannelConfig) -> WebRtcSocketBuilder<MultipleChannels> {
+        self.push_channel(config)
+    }
+}
+
+impl WebRtcSocketBuilder<MultipleChannels> {
+    pub fn add_channel(self, config: ChannelConfig) -> WebRtcSocketBuilder<MultipleChannels> {
+        self.push_channel(config)
+    }
+}
+
+impl<C: BuildablePlurality> WebRtcSocketBuilder<C> {
+    /// Builds the socket and the future that must be spawned (native) or
+    /// awaited (wasm) to drive its signaling and data channels.
+    pub fn build(self) -> (WebRtcSocket, MessageLoopFuture) {
+        let (requests_sender, requests_receiver) = futures_channel::mpsc::unbounded();
+        let (events_sender, events_receiver) = futures_channel::mpsc::unbounded();
+        let (peer_state_tx, peer_state_rx) = futures_channel::mpsc::unbounded();
+        let (id_tx, id_rx) = crossbeam_channel::unbounded();
+
+        let mut channels = Vec::with_capacity(self.channel_configs.len());
+        let mut peer_messages_out_rx = Vec::with_capacity(self.channel_configs.len());
+        let mut messages_from_peers_tx = Vec::with_capacity(self.channel_configs.len());
+
+        for _ in &self.channel_configs {
+            let (out_tx, out_rx) = futures_channel::mpsc::unbounded();
+            let (in_tx, in_rx) = futures_channel::mpsc::unbounded();
+            peer_messages_out_rx.push(out_rx);
+            messages_from_peers_tx.push(in_tx);
+            channels.push(Some(WebRtcChannel {
+                outbox: out_tx,
+                inbox: in_rx,
+            }));
+        }
+
+        let channel_configs = self.channel_configs;
+        let ice_server_config = self.ice_server_config;
+        let keep_alive_interval = self.keep_alive_interval;
+        let max_in_flight_handshakes = self.max_in_flight_handshakes;
+
+        let channels_for_loop = MessageLoopChannels {
+            requests_sender: requests_sender.clone(),
+            events_receiver,
+            peer_messages_out_rx,
+            messages_from_peers_tx,
+            peer_state_tx,
+        };
+
+        let signaling = signaling_loop::<UseSignaller>(
+            self.reconnect_attempts,
+            self.room_urls,
+            requests_receiver,
+            events_sender,
+            self.nostr_keys,
+            self.custom_handler,
+            self.rate_limit_config,
+        );
+
+        let message_loop_future = async move {
+            let message_loop = message_loop::<UseMessenger>(
+                id_tx,
+                &ice_server_config,
+                &channel_configs,
+                channels_for_loop,
+                keep_alive_interval,
+                max_in_flight_handshakes,
+            );
+
+            futures::pin_mut!(signaling);
+            futures::pin_mut!(message_loop);
+
+            futures::select! {
+                result = signaling.fuse() => result.map_err(Error::from),
+                result = message_loop.fuse() => result.map_err(Error::from),
+            }
+        };
+
+        let socket = WebRtcSocket {
+            id_rx,
+            id: None,
+            peer_state_rx,
+            channels,
+            requests_sender,
+        };
+
+        (socket, Box::pin(message_loop_future))
+    }
+}
@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures_util::StreamExt;
+use log::warn;
+
+use crate::webrtc_socket::{
+    error::SignalingError, matchbox_protocol::PeerSignal, messages::PeerId, signal_peer::SignalPeer,
+    ChannelConfig, HandshakeResult, Messenger, Packet, PeerDataSender, RtcIceServerConfig, Signaller,
+};
+
+/// Opens one Nostr relay WebSocket connection via the browser's
+/// `WebSocket` API.
+pub(crate) struct WasmSignaller {
+    socket: ewebsock::WsReceiver,
+    sender: ewebsock::WsSender,
+}
+
+#[async_trait(?Send)]
+impl Signaller for WasmSignaller {
+    async fn new(mut attempts: Option<u16>, room_url: &str) -> Result<Self, SignalingError> {
+        loop {
+            match ewebsock::connect(room_url, ewebsock::Options::default()) {
+                Ok((sender, socket)) => return Ok(Self { socket, sender }),
+                Err(err) => {
+                    if let Some(attempts) = attempts.as_mut() {
+                        if *attempts == 0 {
+                            return Err(SignalingError::ConnectionReset(err));
+                        }
+                        *attempts -= 1;
+                    }
+                    warn!("failed to connect to relay {room_url}: {err}, retrying");
+                }
+            }
+        }
+    }
+
+    async fn send(&mut self, request: String) -> Result<(), SignalingError> {
+        self.sender.send(ewebsock::WsMessage::Text(request));
+        Ok(())
+    }
+
+    async fn next_message(&mut self) -> Result<String, SignalingError> {
+        loop {
+            match self.socket.try_recv() {
+                Some(ewebsock::WsEvent::Message(ewebsock::WsMessage::Text(text))) => return Ok(text),
+                Some(ewebsock::WsEvent::Closed) => {
+                    return Err(SignalingError::ConnectionReset("relay connection closed".to_string()))
+                }
+                Some(ewebsock::WsEvent::Error(err)) => return Err(SignalingError::ConnectionReset(err)),
+                Some(ewebsock::WsEvent::Opened) | Some(ewebsock::WsMessage::Unknown(_)) => continue,
+                Some(_) => return Err(SignalingError::UnknownFormat),
+                None => {
+                    gloo_timers::future::TimeoutFuture::new(10).await;
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct DataChannel {
+    channel: web_sys::RtcDataChannel,
+}
+
+impl PeerDataSender for DataChannel {
+    fn send(&mut self, packet: Packet) -> Result<(), crate::webrtc_socket::error::MessagingError> {
+        self.channel
+            .send_with_u8_array(&packet)
+            .map_err(|err| crate::webrtc_socket::error::MessagingError::Other(format!("{err:?}")))
+    }
+
+    fn buffered_amount(&self) -> usize {
+        self.channel.buffered_amount() as usize
+    }
+}
+
+pub(crate) struct WasmMessenger;
+
+#[async_trait(?Send)]
+impl Messenger for WasmMessenger {
+    type DataChannel = DataChannel;
+    type HandshakeMeta = ();
+
+    async fn offer_handshake(
+        signal_peer: SignalPeer,
+        mut peer_signal_rx: UnboundedReceiver<PeerSignal>,
+        messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+        ice_server_config: &RtcIceServerConfig,
+        channel_configs: &[ChannelConfig],
+    ) -> HandshakeResult<Self::DataChannel, Self::HandshakeMeta> {
+        negotiate(
+            true,
+            signal_peer,
+            &mut peer_signal_rx,
+            messages_from_peers_tx,
+            ice_server_config,
+            channel_configs,
+        )
+        .await
+    }
+
+    async fn accept_handshake(
+        signal_peer: SignalPeer,
+        mut peer_signal_rx: UnboundedReceiver<PeerSignal>,
+        messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+        ice_server_config: &RtcIceServerConfig,
+        channel_configs: &[ChannelConfig],
+    ) -> HandshakeResult<Self::DataChannel, Self::HandshakeMeta> {
+        negotiate(
+            false,
+            signal_peer,
+            &mut peer_signal_rx,
+            messages_from_peers_tx,
+            ice_server_config,
+            channel_configs,
+        )
+        .await
+    }
+
+    async fn peer_loop(peer_uuid: PeerId, _handshake_meta: Self::HandshakeMeta) -> PeerId {
+        peer_uuid
+    }
+}
+
+fn build_rtc_config(ice_server_config: &RtcIceServerConfig) -> web_sys::RtcConfiguration {
+    let mut config = web_sys::RtcConfiguration::new();
+    let ice_server = web_sys::RtcIceServer::new();
+    ice_server.set_urls(&ice_server_config.urls.iter().collect::<js_sys::Array>());
+    if let Some(username) = &ice_server_config.username {
+        ice_server.set_username(username);
+    }
+    if let Some(credential) = &ice_server_config.credential {
+        ice_server.set_credential(credential);
+    }
+    config.set_ice_servers(&js_sys::Array::of1(&ice_server));
+    config
+}
+
+async fn negotiate(
+    offerer: bool,
+    signal_peer: SignalPeer,
+    peer_signal_rx: &mut UnboundedReceiver<PeerSignal>,
+    messages_from_peers_tx: Vec<UnboundedSender<(PeerId, Packet)>>,
+    ice_server_config: &RtcIceServerConfig,
+    channel_configs: &[ChannelConfig],
+) -> HandshakeResult<DataChannel, ()> {
+    let connection = web_sys::RtcPeerConnection::new_with_configuration(&build_rtc_config(ice_server_config))
+        .expect("failed to create peer connection");
+
+    let mut data_channels = Vec::with_capacity(channel_configs.len());
+    for (index, config) in channel_configs.iter().enumerate() {
+        let init = web_sys::RtcDataChannelInit::new();
+        init.set_ordered(config.ordered);
+        if let Some(max_retransmits) = config.max_retransmits {
+            init.set_max_retransmits(max_retransmits);
+        }
+        let channel = connection.create_data_channel_with_data_channel_dict(&format!("matchbox-{index}"), &init);
+
+        let tx = messages_from_peers_tx[index].clone();
+        let peer_id = signal_peer.peer_id();
+        let on_message = wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+            if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                let packet: Packet = js_sys::Uint8Array::new(&buffer).to_vec().into_boxed_slice();
+                let _ = tx.unbounded_send((peer_id, packet));
+            }
+        }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+        channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+
+        data_channels.push(DataChannel { channel });
+    }
+
+    if offerer {
+        let offer = wasm_bindgen_futures::JsFuture::from(connection.create_offer())
+            .await
+            .expect("failed to create offer");
+        let offer: web_sys::RtcSessionDescriptionInit = offer.into();
+        wasm_bindgen_futures::JsFuture::from(connection.set_local_description(&offer))
+            .await
+            .expect("failed to set local description");
+        signal_peer.send(PeerSignal::Offer(
+            connection.local_description().expect("no local description").sdp(),
+        ));
+    }
+
+    while let Some(signal) = peer_signal_rx.next().await {
+        match signal {
+            PeerSignal::Offer(sdp) => {
+                let mut description = web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Offer);
+                description.sdp(&sdp);
+                wasm_bindgen_futures::JsFuture::from(connection.set_remote_description(&description))
+                    .await
+                    .expect("failed to set remote description");
+
+                let answer = wasm_bindgen_futures::JsFuture::from(connection.create_answer())
+                    .await
+                    .expect("failed to create answer");
+                let answer: web_sys::RtcSessionDescriptionInit = answer.into();
+                wasm_bindgen_futures::JsFuture::from(connection.set_local_description(&answer))
+                    .await
+                    .expect("failed to set local description");
+                signal_peer.send(PeerSignal::Answer(
+                    connection.local_description().expect("no local description").sdp(),
+                ));
+            }
+            PeerSignal::Answer(sdp) => {
+                let mut description = web_sys::RtcSessionDescriptionInit::new(web_sys::RtcSdpType::Answer);
+                description.sdp(&sdp);
+                wasm_bindgen_futures::JsFuture::from(connection.set_remote_description(&description))
+                    .await
+                    .expect("failed to set remote description");
+                break;
+            }
+            PeerSignal::IceCandidate(candidate) => {
+                let init = web_sys::RtcIceCandidateInit::new(&candidate);
+                let _ = connection.add_ice_candidate_with_opt_rtc_ice_candidate_init(Some(&init));
+            }
+        }
+    }
+
+    HandshakeResult {
+        peer_id: signal_peer.peer_id(),
+        data_channels,
+        metadata: (),
+    }
+}